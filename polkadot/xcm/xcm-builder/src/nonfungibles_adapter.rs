@@ -17,10 +17,14 @@
 //! Adapters to work with [`frame_support::traits::tokens::nonfungibles`] through XCM.
 
 use crate::{AssetChecking, MintLocation};
+use alloc::vec::Vec;
 use core::{fmt::Debug, marker::PhantomData, result};
 use frame_support::{
 	ensure,
-	traits::{tokens::nonfungibles, Get},
+	traits::{
+		tokens::{nonfungibles, nonfungibles_v2},
+		Get,
+	},
 };
 use xcm::latest::prelude::*;
 use xcm_executor::traits::{
@@ -236,6 +240,7 @@ where
 		);
 		// Check we handle this asset.
 		let (class, instance) = Matcher::matches_nonfungibles(what)?;
+		ensure!(Assets::owner(&class, &instance).is_none(), XcmError::NotDepositable);
 		let who = AccountIdConverter::convert_location(who)
 			.ok_or(MatchError::AccountIdConversionFailed)?;
 		Assets::mint_into(&class, &instance, &who).map_err(|e| {
@@ -260,6 +265,8 @@ where
 		let who = AccountIdConverter::convert_location(who)
 			.ok_or(MatchError::AccountIdConversionFailed)?;
 		let (class, instance) = Matcher::matches_nonfungibles(what)?;
+		let owner = Assets::owner(&class, &instance).ok_or(XcmError::AssetNotFound)?;
+		ensure!(owner == who, XcmError::NotWithdrawable);
 		Assets::burn(&class, &instance, Some(&who)).map_err(|e| {
 			tracing::debug!(target: LOG_TARGET, ?e, ?class, ?instance, ?who, "Failed to burn asset");
 			XcmError::FailedToTransactAsset(e.into())
@@ -385,3 +392,896 @@ where
 		)
 	}
 }
+
+/// [`TransactAsset`] implementation that allows the use of a [`nonfungibles`] implementation for
+/// handling an asset in the XCM executor, where collections whose items originate on another
+/// chain are created permissionlessly on first deposit rather than requiring a prior privileged
+/// `create_collection` call.
+///
+/// This mirrors the way foreign fungible assets let a sibling parachain's tokens arrive without a
+/// governance step: the first time an item of an unrecognised `CollectionId` is deposited (or
+/// checked in as part of a tracked teleport), the collection is created on the fly and owned by a
+/// sovereign "foreign creator" account derived from the location the item arrived from
+/// (`XcmContext::origin`) via `CreateCollectionOrigin`. Everything else behaves exactly like
+/// [`NonFungiblesAdapter`].
+pub struct ForeignNonFungiblesAdapter<
+	Assets,
+	Matcher,
+	AccountIdConverter,
+	AccountId,
+	CheckAsset,
+	CheckingAccount,
+	CreateCollectionOrigin,
+>(
+	PhantomData<(
+		Assets,
+		Matcher,
+		AccountIdConverter,
+		AccountId,
+		CheckAsset,
+		CheckingAccount,
+		CreateCollectionOrigin,
+	)>,
+)
+where
+	Assets: nonfungibles::Transfer<AccountId>,
+	Assets::CollectionId: Debug,
+	Assets::ItemId: Debug;
+impl<
+		Assets: nonfungibles::Mutate<AccountId>
+			+ nonfungibles::Transfer<AccountId>
+			+ nonfungibles::Create<AccountId>,
+		Matcher: MatchesNonFungibles<Assets::CollectionId, Assets::ItemId>,
+		AccountIdConverter: ConvertLocation<AccountId>,
+		AccountId: Clone + Eq + Debug, /* can't get away without it since Currency is generic
+		                                * over it. */
+		CheckAsset: AssetChecking<Assets::CollectionId>,
+		CheckingAccount: Get<Option<AccountId>>,
+		CreateCollectionOrigin: ConvertLocation<AccountId>,
+	>
+	ForeignNonFungiblesAdapter<
+		Assets,
+		Matcher,
+		AccountIdConverter,
+		AccountId,
+		CheckAsset,
+		CheckingAccount,
+		CreateCollectionOrigin,
+	>
+where
+	Assets::CollectionId: Debug,
+	Assets::ItemId: Debug,
+{
+	/// Create `class` permissionlessly, owned by the sovereign account of `origin`, for an item
+	/// arriving from another chain whose collection doesn't exist here yet.
+	fn create_foreign_collection(class: &Assets::CollectionId, origin: Option<&Location>) -> XcmResult {
+		let origin = origin.ok_or(MatchError::AccountIdConversionFailed)?;
+		let foreign_creator = CreateCollectionOrigin::convert_location(origin)
+			.ok_or(MatchError::AccountIdConversionFailed)?;
+		Assets::create_collection(class, &foreign_creator, &foreign_creator).map_err(|e| {
+			tracing::debug!(target: LOG_TARGET, ?e, ?class, ?foreign_creator, "Failed to create foreign collection");
+			XcmError::FailedToTransactAsset(e.into())
+		})
+	}
+
+	/// Unlike the plain [`NonFungiblesMutateAdapter`]'s `can_accrue_checked`, this also has to
+	/// rule out the case where `check_in` would need to create the collection on the fly: since
+	/// `check_in` returns `()` and can't report a failure, anything we can determine up front
+	/// about whether that creation would succeed has to be checked here instead.
+	fn can_accrue_checked(
+		class: Assets::CollectionId,
+		instance: Assets::ItemId,
+		origin: Option<&Location>,
+	) -> XcmResult {
+		ensure!(Assets::owner(&class, &instance).is_none(), XcmError::NotDepositable);
+		if Assets::collection_owner(&class).is_none() {
+			let origin = origin.ok_or(MatchError::AccountIdConversionFailed)?;
+			ensure!(
+				CreateCollectionOrigin::convert_location(origin).is_some(),
+				MatchError::AccountIdConversionFailed
+			);
+		}
+		Ok(())
+	}
+	fn can_reduce_checked(class: Assets::CollectionId, instance: Assets::ItemId) -> XcmResult {
+		if let Some(checking_account) = CheckingAccount::get() {
+			// This is an asset whose teleports we track.
+			let owner = Assets::owner(&class, &instance);
+			ensure!(owner == Some(checking_account), XcmError::NotWithdrawable);
+			ensure!(Assets::can_transfer(&class, &instance), XcmError::NotWithdrawable);
+		}
+		Ok(())
+	}
+	/// Unlike the plain [`NonFungiblesMutateAdapter`], this understands collections it does not
+	/// yet own: a teleport-tracked item of a collection originating elsewhere causes the
+	/// collection to be created here first, the same way `deposit_asset` does. `can_check_in`
+	/// having called `can_accrue_checked` only rules out a misresolved `origin`; collection
+	/// creation can still fail for reasons only visible at execution time (e.g. the foreign
+	/// creator's sovereign account lacking the deposit), so unlike `reduce_checked`'s `burn` this
+	/// does not assume `mint_into` cannot fail — it logs and leaves the item un-minted instead.
+	fn accrue_checked(class: Assets::CollectionId, instance: Assets::ItemId, origin: Option<&Location>) {
+		if let Some(checking_account) = CheckingAccount::get() {
+			if Assets::collection_owner(&class).is_none() {
+				if let Err(e) = Self::create_foreign_collection(&class, origin) {
+					tracing::debug!(target: LOG_TARGET, ?e, ?class, "Failed to create foreign collection during check-in");
+					return;
+				}
+			}
+			if let Err(e) = Assets::mint_into(&class, &instance, &checking_account) {
+				tracing::debug!(target: LOG_TARGET, ?e, ?class, ?instance, "Failed to mint checked-in asset");
+			}
+		}
+	}
+	fn reduce_checked(class: Assets::CollectionId, instance: Assets::ItemId) {
+		let ok = Assets::burn(&class, &instance, None).is_ok();
+		debug_assert!(ok, "`can_check_in` must have returned `true` immediately prior; qed");
+	}
+}
+
+impl<
+		Assets: nonfungibles::Mutate<AccountId>
+			+ nonfungibles::Transfer<AccountId>
+			+ nonfungibles::Create<AccountId>,
+		Matcher: MatchesNonFungibles<Assets::CollectionId, Assets::ItemId>,
+		AccountIdConverter: ConvertLocation<AccountId>,
+		AccountId: Clone + Eq + Debug, /* can't get away without it since Currency is generic
+		                                * over it. */
+		CheckAsset: AssetChecking<Assets::CollectionId>,
+		CheckingAccount: Get<Option<AccountId>>,
+		CreateCollectionOrigin: ConvertLocation<AccountId>,
+	> TransactAsset
+	for ForeignNonFungiblesAdapter<
+		Assets,
+		Matcher,
+		AccountIdConverter,
+		AccountId,
+		CheckAsset,
+		CheckingAccount,
+		CreateCollectionOrigin,
+	>
+where
+	Assets::CollectionId: Debug,
+	Assets::ItemId: Debug,
+{
+	fn can_check_in(origin: &Location, what: &Asset, context: &XcmContext) -> XcmResult {
+		tracing::trace!(
+			target: LOG_TARGET,
+			?origin,
+			?what,
+			?context,
+			"can_check_in",
+		);
+		let (class, instance) = Matcher::matches_nonfungibles(what)?;
+		match CheckAsset::asset_checking(&class) {
+			Some(MintLocation::Local) => Self::can_reduce_checked(class, instance),
+			Some(MintLocation::NonLocal) => Self::can_accrue_checked(class, instance, Some(origin)),
+			_ => Ok(()),
+		}
+	}
+
+	fn check_in(origin: &Location, what: &Asset, context: &XcmContext) {
+		tracing::trace!(
+			target: LOG_TARGET,
+			?origin,
+			?what,
+			?context,
+			"check_in",
+		);
+		if let Ok((class, instance)) = Matcher::matches_nonfungibles(what) {
+			match CheckAsset::asset_checking(&class) {
+				Some(MintLocation::Local) => Self::reduce_checked(class, instance),
+				// `origin` is where this item travelled from, so it's also where a not-yet-seen
+				// collection should be created from.
+				Some(MintLocation::NonLocal) => Self::accrue_checked(class, instance, Some(origin)),
+				_ => (),
+			}
+		}
+	}
+
+	fn can_check_out(dest: &Location, what: &Asset, context: &XcmContext) -> XcmResult {
+		tracing::trace!(
+			target: LOG_TARGET,
+			?dest,
+			?what,
+			?context,
+			"can_check_out",
+		);
+		let (class, instance) = Matcher::matches_nonfungibles(what)?;
+		match CheckAsset::asset_checking(&class) {
+			Some(MintLocation::Local) => Self::can_accrue_checked(class, instance, None),
+			Some(MintLocation::NonLocal) => Self::can_reduce_checked(class, instance),
+			_ => Ok(()),
+		}
+	}
+
+	fn check_out(dest: &Location, what: &Asset, context: &XcmContext) {
+		tracing::trace!(
+			target: LOG_TARGET,
+			?dest,
+			?what,
+			?context,
+			"check_out",
+		);
+		if let Ok((class, instance)) = Matcher::matches_nonfungibles(what) {
+			match CheckAsset::asset_checking(&class) {
+				// A `Local`-tracked item being checked out is, by definition, already in a
+				// collection we own; there is no sensible "origin" to create one from here.
+				Some(MintLocation::Local) => Self::accrue_checked(class, instance, None),
+				Some(MintLocation::NonLocal) => Self::reduce_checked(class, instance),
+				_ => (),
+			}
+		}
+	}
+
+	fn deposit_asset(what: &Asset, who: &Location, context: Option<&XcmContext>) -> XcmResult {
+		tracing::trace!(
+			target: LOG_TARGET,
+			?what,
+			?who,
+			?context,
+			"deposit_asset",
+		);
+		// Check we handle this asset.
+		let (class, instance) = Matcher::matches_nonfungibles(what)?;
+		ensure!(Assets::owner(&class, &instance).is_none(), XcmError::NotDepositable);
+		if Assets::collection_owner(&class).is_none() {
+			// This collection doesn't exist locally yet: it must be originating from another
+			// chain, so create it permissionlessly, owned by a sovereign account of wherever
+			// this item came from.
+			let origin = context.and_then(|context| context.origin.as_ref());
+			Self::create_foreign_collection(&class, origin)?;
+		}
+		let who = AccountIdConverter::convert_location(who)
+			.ok_or(MatchError::AccountIdConversionFailed)?;
+		Assets::mint_into(&class, &instance, &who).map_err(|e| {
+			tracing::debug!(target: LOG_TARGET, ?e, ?class, ?instance, ?who, "Failed to mint asset");
+			XcmError::FailedToTransactAsset(e.into())
+		})
+	}
+
+	fn withdraw_asset(
+		what: &Asset,
+		who: &Location,
+		maybe_context: Option<&XcmContext>,
+	) -> result::Result<xcm_executor::AssetsInHolding, XcmError> {
+		NonFungiblesMutateAdapter::<
+			Assets,
+			Matcher,
+			AccountIdConverter,
+			AccountId,
+			CheckAsset,
+			CheckingAccount,
+		>::withdraw_asset(what, who, maybe_context)
+	}
+
+	fn transfer_asset(
+		what: &Asset,
+		from: &Location,
+		to: &Location,
+		context: &XcmContext,
+	) -> result::Result<xcm_executor::AssetsInHolding, XcmError> {
+		NonFungiblesTransferAdapter::<Assets, Matcher, AccountIdConverter, AccountId>::transfer_asset(
+			what, from, to, context,
+		)
+	}
+}
+
+/// Controls which of an item's attributes travel with it across a teleport or transfer handled by
+/// [`NonFungiblesV2Adapter`].
+///
+/// Attribute keys not in [`Self::whitelisted_keys`] are dropped, since carrying arbitrary
+/// chain-specific attributes across a bridge is neither safe nor generally meaningful. The
+/// `Matcher` paired with `NonFungiblesV2Adapter` is expected to derive the collection and item id
+/// purely from the `Asset`'s `AssetId`; the `AssetInstance` that travels with it is reserved for
+/// the payload produced by [`Self::encode`]/consumed by [`Self::decode`].
+pub trait AttributeCodec {
+	/// The attribute keys eligible to cross chains.
+	fn whitelisted_keys() -> &'static [&'static [u8]];
+	/// Encode the given whitelisted `(key, value)` attribute pairs into an `AssetInstance`
+	/// payload.
+	fn encode(attributes: &[(Vec<u8>, Vec<u8>)]) -> AssetInstance;
+	/// Decode an `AssetInstance` payload produced by [`Self::encode`] back into `(key, value)`
+	/// attribute pairs.
+	fn decode(instance: &AssetInstance) -> Vec<(Vec<u8>, Vec<u8>)>;
+}
+
+/// [`TransactAsset`] implementation that allows the use of a
+/// [`frame_support::traits::tokens::nonfungibles_v2`] implementation for handling an asset in the
+/// XCM executor, carrying the item's whitelisted attributes along with it so that a teleport or
+/// transfer does not silently drop its metadata the way the `v1`-based adapters in this module do.
+///
+/// Works for everything the `v1` [`NonFungiblesAdapter`] does; `ItemConfig` is the configuration
+/// `Assets::mint_into` requires when minting a freshly arrived item and is constructed with
+/// `Default`, matching how most `nonfungibles_v2` implementations treat a deposited item as
+/// unconfigured until its attributes are restored.
+pub struct NonFungiblesV2Adapter<
+	Assets,
+	Matcher,
+	AccountIdConverter,
+	AccountId,
+	CheckAsset,
+	CheckingAccount,
+	ItemConfig,
+	Codec,
+>(
+	PhantomData<(
+		Assets,
+		Matcher,
+		AccountIdConverter,
+		AccountId,
+		CheckAsset,
+		CheckingAccount,
+		ItemConfig,
+		Codec,
+	)>,
+);
+
+impl<
+		Assets: nonfungibles_v2::Mutate<AccountId, ItemConfig> + nonfungibles_v2::Transfer<AccountId>,
+		Matcher: MatchesNonFungibles<Assets::CollectionId, Assets::ItemId>,
+		AccountIdConverter: ConvertLocation<AccountId>,
+		AccountId: Clone + Eq + Debug,
+		CheckAsset: AssetChecking<Assets::CollectionId>,
+		CheckingAccount: Get<Option<AccountId>>,
+		ItemConfig: Default,
+		Codec: AttributeCodec,
+	>
+	NonFungiblesV2Adapter<
+		Assets,
+		Matcher,
+		AccountIdConverter,
+		AccountId,
+		CheckAsset,
+		CheckingAccount,
+		ItemConfig,
+		Codec,
+	>
+where
+	Assets::CollectionId: Debug,
+	Assets::ItemId: Debug,
+{
+	fn attributes_of(class: &Assets::CollectionId, instance: &Assets::ItemId) -> AssetInstance {
+		let attributes = Codec::whitelisted_keys()
+			.iter()
+			.filter_map(|key| {
+				nonfungibles_v2::Inspect::attribute(class, instance, key)
+					.map(|value| (key.to_vec(), value))
+			})
+			.collect::<Vec<_>>();
+		Codec::encode(&attributes)
+	}
+
+	fn restore_attributes(
+		class: &Assets::CollectionId,
+		instance: &Assets::ItemId,
+		payload: &AssetInstance,
+	) -> XcmResult {
+		let whitelisted = Codec::whitelisted_keys();
+		for (key, value) in Codec::decode(payload) {
+			// `payload` is remote-controlled XCM data, so re-check the whitelist here rather than
+			// trusting `Codec::decode` to have enforced it.
+			if !whitelisted.iter().any(|allowed| allowed == &key.as_slice()) {
+				tracing::debug!(target: LOG_TARGET, ?class, ?instance, ?key, "Dropping non-whitelisted attribute");
+				continue
+			}
+			Assets::set_attribute(class, instance, &key, &value).map_err(|e| {
+				tracing::debug!(target: LOG_TARGET, ?e, ?class, ?instance, "Failed to restore attribute");
+				XcmError::FailedToTransactAsset(e.into())
+			})?;
+		}
+		Ok(())
+	}
+
+	fn can_accrue_checked(class: Assets::CollectionId, instance: Assets::ItemId) -> XcmResult {
+		ensure!(
+			nonfungibles_v2::Inspect::owner(&class, &instance).is_none(),
+			XcmError::NotDepositable
+		);
+		Ok(())
+	}
+	fn can_reduce_checked(class: Assets::CollectionId, instance: Assets::ItemId) -> XcmResult {
+		if let Some(checking_account) = CheckingAccount::get() {
+			// This is an asset whose teleports we track.
+			let owner = nonfungibles_v2::Inspect::owner(&class, &instance);
+			ensure!(owner == Some(checking_account), XcmError::NotWithdrawable);
+			ensure!(
+				nonfungibles_v2::Inspect::can_transfer(&class, &instance),
+				XcmError::NotWithdrawable
+			);
+		}
+		Ok(())
+	}
+	fn accrue_checked(class: Assets::CollectionId, instance: Assets::ItemId) {
+		if let Some(checking_account) = CheckingAccount::get() {
+			let ok = Assets::mint_into(&class, &instance, &checking_account, &ItemConfig::default(), false)
+				.is_ok();
+			debug_assert!(ok, "`mint_into` cannot generally fail; qed");
+		}
+	}
+	fn reduce_checked(class: Assets::CollectionId, instance: Assets::ItemId) {
+		let ok = Assets::burn(&class, &instance, None).is_ok();
+		debug_assert!(ok, "`can_check_in` must have returned `true` immediately prior; qed");
+	}
+}
+
+impl<
+		Assets: nonfungibles_v2::Mutate<AccountId, ItemConfig> + nonfungibles_v2::Transfer<AccountId>,
+		Matcher: MatchesNonFungibles<Assets::CollectionId, Assets::ItemId>,
+		AccountIdConverter: ConvertLocation<AccountId>,
+		AccountId: Clone + Eq + Debug,
+		CheckAsset: AssetChecking<Assets::CollectionId>,
+		CheckingAccount: Get<Option<AccountId>>,
+		ItemConfig: Default,
+		Codec: AttributeCodec,
+	> TransactAsset
+	for NonFungiblesV2Adapter<
+		Assets,
+		Matcher,
+		AccountIdConverter,
+		AccountId,
+		CheckAsset,
+		CheckingAccount,
+		ItemConfig,
+		Codec,
+	>
+where
+	Assets::CollectionId: Debug,
+	Assets::ItemId: Debug,
+{
+	fn can_check_in(origin: &Location, what: &Asset, context: &XcmContext) -> XcmResult {
+		tracing::trace!(
+			target: LOG_TARGET,
+			?origin,
+			?what,
+			?context,
+			"can_check_in",
+		);
+		let (class, instance) = Matcher::matches_nonfungibles(what)?;
+		match CheckAsset::asset_checking(&class) {
+			Some(MintLocation::Local) => Self::can_reduce_checked(class, instance),
+			Some(MintLocation::NonLocal) => Self::can_accrue_checked(class, instance),
+			_ => Ok(()),
+		}
+	}
+
+	fn check_in(origin: &Location, what: &Asset, context: &XcmContext) {
+		tracing::trace!(
+			target: LOG_TARGET,
+			?origin,
+			?what,
+			?context,
+			"check_in",
+		);
+		if let Ok((class, instance)) = Matcher::matches_nonfungibles(what) {
+			match CheckAsset::asset_checking(&class) {
+				Some(MintLocation::Local) => Self::reduce_checked(class, instance),
+				Some(MintLocation::NonLocal) => Self::accrue_checked(class, instance),
+				_ => (),
+			}
+		}
+	}
+
+	fn can_check_out(dest: &Location, what: &Asset, context: &XcmContext) -> XcmResult {
+		tracing::trace!(
+			target: LOG_TARGET,
+			?dest,
+			?what,
+			?context,
+			"can_check_out",
+		);
+		let (class, instance) = Matcher::matches_nonfungibles(what)?;
+		match CheckAsset::asset_checking(&class) {
+			Some(MintLocation::Local) => Self::can_accrue_checked(class, instance),
+			Some(MintLocation::NonLocal) => Self::can_reduce_checked(class, instance),
+			_ => Ok(()),
+		}
+	}
+
+	fn check_out(dest: &Location, what: &Asset, context: &XcmContext) {
+		tracing::trace!(
+			target: LOG_TARGET,
+			?dest,
+			?what,
+			?context,
+			"check_out",
+		);
+		if let Ok((class, instance)) = Matcher::matches_nonfungibles(what) {
+			match CheckAsset::asset_checking(&class) {
+				Some(MintLocation::Local) => Self::accrue_checked(class, instance),
+				Some(MintLocation::NonLocal) => Self::reduce_checked(class, instance),
+				_ => (),
+			}
+		}
+	}
+
+	fn deposit_asset(what: &Asset, who: &Location, context: Option<&XcmContext>) -> XcmResult {
+		tracing::trace!(
+			target: LOG_TARGET,
+			?what,
+			?who,
+			?context,
+			"deposit_asset",
+		);
+		let (class, instance) = Matcher::matches_nonfungibles(what)?;
+		ensure!(
+			nonfungibles_v2::Inspect::owner(&class, &instance).is_none(),
+			XcmError::NotDepositable
+		);
+		let who = AccountIdConverter::convert_location(who)
+			.ok_or(MatchError::AccountIdConversionFailed)?;
+		Assets::mint_into(&class, &instance, &who, &ItemConfig::default(), false).map_err(|e| {
+			tracing::debug!(target: LOG_TARGET, ?e, ?class, ?instance, ?who, "Failed to mint asset");
+			XcmError::FailedToTransactAsset(e.into())
+		})?;
+		if let Fungibility::NonFungible(ref payload) = what.fun {
+			Self::restore_attributes(&class, &instance, payload)?;
+		}
+		Ok(())
+	}
+
+	fn withdraw_asset(
+		what: &Asset,
+		who: &Location,
+		maybe_context: Option<&XcmContext>,
+	) -> result::Result<xcm_executor::AssetsInHolding, XcmError> {
+		tracing::trace!(
+			target: LOG_TARGET,
+			?what,
+			?who,
+			?maybe_context,
+			"withdraw_asset",
+		);
+		let who = AccountIdConverter::convert_location(who)
+			.ok_or(MatchError::AccountIdConversionFailed)?;
+		let (class, instance) = Matcher::matches_nonfungibles(what)?;
+		let owner =
+			nonfungibles_v2::Inspect::owner(&class, &instance).ok_or(XcmError::AssetNotFound)?;
+		ensure!(owner == who, XcmError::NotWithdrawable);
+		let payload = Self::attributes_of(&class, &instance);
+		Assets::burn(&class, &instance, Some(&who)).map_err(|e| {
+			tracing::debug!(target: LOG_TARGET, ?e, ?class, ?instance, ?who, "Failed to burn asset");
+			XcmError::FailedToTransactAsset(e.into())
+		})?;
+		Ok(Asset { id: what.id.clone(), fun: Fungibility::NonFungible(payload) }.into())
+	}
+
+	fn transfer_asset(
+		what: &Asset,
+		from: &Location,
+		to: &Location,
+		context: &XcmContext,
+	) -> result::Result<xcm_executor::AssetsInHolding, XcmError> {
+		tracing::trace!(
+			target: LOG_TARGET,
+			?what,
+			?from,
+			?to,
+			?context,
+			"transfer_asset",
+		);
+		let (class, instance) = Matcher::matches_nonfungibles(what)?;
+		let destination = AccountIdConverter::convert_location(to)
+			.ok_or(MatchError::AccountIdConversionFailed)?;
+		nonfungibles_v2::Transfer::transfer(&class, &instance, &destination).map_err(|e| {
+			tracing::debug!(target: LOG_TARGET, ?e, ?class, ?instance, ?destination, "Failed to transfer asset");
+			XcmError::FailedToTransactAsset(e.into())
+		})?;
+		Ok(what.clone().into())
+	}
+}
+
+/// [`TransactAsset`] implementation that allows the use of a
+/// [`frame_support::traits::tokens::nonfungibles_v2`] implementation for handling an asset in the
+/// XCM executor under reserve-transfer semantics: rather than burning the item as
+/// [`NonFungiblesMutateAdapter`] does, `withdraw_asset` locks it in place by transferring it to a
+/// configurable `ReserveAccount` and disabling its transferability, leaving the original item
+/// intact while a derivative is minted on the remote chain. A matching `deposit_asset` for the
+/// return leg re-enables transfer and hands the item back to its beneficiary.
+///
+/// An item is considered locked exactly when it is owned by `ReserveAccount` and not
+/// transferable, so there is no separate bookkeeping to keep in sync with the underlying
+/// `Assets` implementation; `withdraw_asset` requiring the caller to currently own the item is
+/// what prevents the same item from being reserve-transferred out twice.
+pub struct NonFungiblesReserveAdapter<Assets, Matcher, AccountIdConverter, AccountId, ReserveAccount>(
+	PhantomData<(Assets, Matcher, AccountIdConverter, AccountId, ReserveAccount)>,
+)
+where
+	Assets: nonfungibles_v2::Transfer<AccountId>,
+	Assets::CollectionId: Debug,
+	Assets::ItemId: Debug;
+
+impl<
+		Assets: nonfungibles_v2::Transfer<AccountId>,
+		Matcher: MatchesNonFungibles<Assets::CollectionId, Assets::ItemId>,
+		AccountIdConverter: ConvertLocation<AccountId>,
+		AccountId: Clone + Eq + Debug,
+		ReserveAccount: Get<AccountId>,
+	> NonFungiblesReserveAdapter<Assets, Matcher, AccountIdConverter, AccountId, ReserveAccount>
+where
+	Assets::CollectionId: Debug,
+	Assets::ItemId: Debug,
+{
+	fn is_locked(class: &Assets::CollectionId, instance: &Assets::ItemId) -> bool {
+		nonfungibles_v2::Inspect::owner(class, instance) == Some(ReserveAccount::get()) &&
+			!nonfungibles_v2::Inspect::can_transfer(class, instance)
+	}
+}
+
+impl<
+		Assets: nonfungibles_v2::Transfer<AccountId>,
+		Matcher: MatchesNonFungibles<Assets::CollectionId, Assets::ItemId>,
+		AccountIdConverter: ConvertLocation<AccountId>,
+		AccountId: Clone + Eq + Debug,
+		ReserveAccount: Get<AccountId>,
+	> TransactAsset
+	for NonFungiblesReserveAdapter<Assets, Matcher, AccountIdConverter, AccountId, ReserveAccount>
+where
+	Assets::CollectionId: Debug,
+	Assets::ItemId: Debug,
+{
+	fn deposit_asset(what: &Asset, who: &Location, context: Option<&XcmContext>) -> XcmResult {
+		tracing::trace!(
+			target: LOG_TARGET,
+			?what,
+			?who,
+			?context,
+			"deposit_asset",
+		);
+		// Check we handle this asset.
+		let (class, instance) = Matcher::matches_nonfungibles(what)?;
+		ensure!(Self::is_locked(&class, &instance), XcmError::NotDepositable);
+		let who = AccountIdConverter::convert_location(who)
+			.ok_or(MatchError::AccountIdConversionFailed)?;
+		// Lift the transfer restriction first: a locked item is by definition not transferable,
+		// so it can only be moved out of `ReserveAccount` once transfer is enabled again.
+		Assets::enable_transfer(&class, &instance).map_err(|e| {
+			tracing::debug!(target: LOG_TARGET, ?e, ?class, ?instance, "Failed to unlock reserved item");
+			XcmError::FailedToTransactAsset(e.into())
+		})?;
+		Assets::transfer(&class, &instance, &who).map_err(|e| {
+			tracing::debug!(target: LOG_TARGET, ?e, ?class, ?instance, ?who, "Failed to return reserved item");
+			XcmError::FailedToTransactAsset(e.into())
+		})
+	}
+
+	fn withdraw_asset(
+		what: &Asset,
+		who: &Location,
+		maybe_context: Option<&XcmContext>,
+	) -> result::Result<xcm_executor::AssetsInHolding, XcmError> {
+		tracing::trace!(
+			target: LOG_TARGET,
+			?what,
+			?who,
+			?maybe_context,
+			"withdraw_asset",
+		);
+		let who = AccountIdConverter::convert_location(who)
+			.ok_or(MatchError::AccountIdConversionFailed)?;
+		let (class, instance) = Matcher::matches_nonfungibles(what)?;
+		let owner = nonfungibles_v2::Inspect::owner(&class, &instance).ok_or(XcmError::AssetNotFound)?;
+		ensure!(owner == who, XcmError::NotWithdrawable);
+		let reserve_account = ReserveAccount::get();
+		// Move the item into `ReserveAccount` first and only then disable its transfer: once
+		// transfer is disabled the item can no longer be transferred.
+		Assets::transfer(&class, &instance, &reserve_account).map_err(|e| {
+			tracing::debug!(target: LOG_TARGET, ?e, ?class, ?instance, "Failed to lock reserved item");
+			XcmError::FailedToTransactAsset(e.into())
+		})?;
+		Assets::disable_transfer(&class, &instance).map_err(|e| {
+			tracing::debug!(target: LOG_TARGET, ?e, ?class, ?instance, "Failed to disable transfer of reserved item");
+			XcmError::FailedToTransactAsset(e.into())
+		})?;
+		Ok(what.clone().into())
+	}
+}
+
+/// Matches an [`Asset`] that denotes "every item of a collection currently owned by the sender",
+/// as opposed to a single `(class, instance)` pair, letting [`BulkNonFungiblesAdapter`] move or
+/// withdraw a whole collection with one XCM instruction instead of one per item.
+pub trait BulkMatcher<CollectionId, ItemId> {
+	/// If `what` is such a collection-wildcard, return the collection it refers to.
+	fn matches_collection(what: &Asset) -> result::Result<CollectionId, MatchError>;
+	/// Reconstruct the concrete [`Asset`] representing a single `item` of `collection`, for
+	/// inclusion in the [`xcm_executor::AssetsInHolding`] returned by a bulk operation.
+	fn asset_for(collection: &CollectionId, item: &ItemId) -> Asset;
+}
+
+/// [`TransactAsset`] implementation built on [`NonFungiblesMutateAdapter`] and
+/// [`NonFungiblesTransferAdapter`] that additionally understands collection-wildcard `Asset`s: if
+/// `what` doesn't match a single item via `Matcher` but matches a whole collection via
+/// `BulkItems`, `transfer_asset`/`withdraw_asset` apply the operation to every item of that
+/// collection owned by the sender, up to `MaxItemsPerOp`, rather than requiring one XCM
+/// instruction per item.
+///
+/// `MaxItemsPerOp` exists so a malicious or oversized collection can't be used to make a single
+/// XCM instruction do unboundedly much work; the enumeration itself is bounded to one more than
+/// the cap so an oversized collection is rejected without reading it in full. Since each item is
+/// mutated independently, a failure partway through does not roll back items already moved: the
+/// returned [`xcm_executor::AssetsInHolding`] always reflects exactly what succeeded, so no item
+/// is ever destroyed/moved without being accounted for. `can_check_in`/`can_check_out`/
+/// `deposit_asset` are unaffected by bulk operations and simply delegate to
+/// [`NonFungiblesMutateAdapter`].
+pub struct BulkNonFungiblesAdapter<
+	Assets,
+	Matcher,
+	BulkItems,
+	MaxItemsPerOp,
+	AccountIdConverter,
+	AccountId,
+	CheckAsset,
+	CheckingAccount,
+>(
+	PhantomData<(
+		Assets,
+		Matcher,
+		BulkItems,
+		MaxItemsPerOp,
+		AccountIdConverter,
+		AccountId,
+		CheckAsset,
+		CheckingAccount,
+	)>,
+)
+where
+	Assets: nonfungibles::Transfer<AccountId>,
+	Assets::CollectionId: Debug,
+	Assets::ItemId: Debug;
+
+impl<
+		Assets: nonfungibles::Mutate<AccountId>
+			+ nonfungibles::Transfer<AccountId>
+			+ nonfungibles::InspectEnumerable<AccountId>,
+		Matcher: MatchesNonFungibles<Assets::CollectionId, Assets::ItemId>,
+		BulkItems: BulkMatcher<Assets::CollectionId, Assets::ItemId>,
+		MaxItemsPerOp: Get<u32>,
+		AccountIdConverter: ConvertLocation<AccountId>,
+		AccountId: Clone + Eq + Debug,
+		CheckAsset: AssetChecking<Assets::CollectionId>,
+		CheckingAccount: Get<Option<AccountId>>,
+	> TransactAsset
+	for BulkNonFungiblesAdapter<
+		Assets,
+		Matcher,
+		BulkItems,
+		MaxItemsPerOp,
+		AccountIdConverter,
+		AccountId,
+		CheckAsset,
+		CheckingAccount,
+	>
+where
+	Assets::CollectionId: Debug,
+	Assets::ItemId: Debug,
+{
+	fn can_check_in(origin: &Location, what: &Asset, context: &XcmContext) -> XcmResult {
+		NonFungiblesMutateAdapter::<
+			Assets,
+			Matcher,
+			AccountIdConverter,
+			AccountId,
+			CheckAsset,
+			CheckingAccount,
+		>::can_check_in(origin, what, context)
+	}
+
+	fn check_in(origin: &Location, what: &Asset, context: &XcmContext) {
+		NonFungiblesMutateAdapter::<
+			Assets,
+			Matcher,
+			AccountIdConverter,
+			AccountId,
+			CheckAsset,
+			CheckingAccount,
+		>::check_in(origin, what, context)
+	}
+
+	fn can_check_out(dest: &Location, what: &Asset, context: &XcmContext) -> XcmResult {
+		NonFungiblesMutateAdapter::<
+			Assets,
+			Matcher,
+			AccountIdConverter,
+			AccountId,
+			CheckAsset,
+			CheckingAccount,
+		>::can_check_out(dest, what, context)
+	}
+
+	fn check_out(dest: &Location, what: &Asset, context: &XcmContext) {
+		NonFungiblesMutateAdapter::<
+			Assets,
+			Matcher,
+			AccountIdConverter,
+			AccountId,
+			CheckAsset,
+			CheckingAccount,
+		>::check_out(dest, what, context)
+	}
+
+	fn deposit_asset(what: &Asset, who: &Location, context: Option<&XcmContext>) -> XcmResult {
+		NonFungiblesMutateAdapter::<
+			Assets,
+			Matcher,
+			AccountIdConverter,
+			AccountId,
+			CheckAsset,
+			CheckingAccount,
+		>::deposit_asset(what, who, context)
+	}
+
+	fn withdraw_asset(
+		what: &Asset,
+		who: &Location,
+		maybe_context: Option<&XcmContext>,
+	) -> result::Result<xcm_executor::AssetsInHolding, XcmError> {
+		if Matcher::matches_nonfungibles(what).is_ok() {
+			return NonFungiblesMutateAdapter::<
+				Assets,
+				Matcher,
+				AccountIdConverter,
+				AccountId,
+				CheckAsset,
+				CheckingAccount,
+			>::withdraw_asset(what, who, maybe_context)
+		}
+		let class = BulkItems::matches_collection(what)?;
+		let owner =
+			AccountIdConverter::convert_location(who).ok_or(MatchError::AccountIdConversionFailed)?;
+		// Bound the enumeration itself: stop reading storage as soon as we know there are more
+		// items than `MaxItemsPerOp` allows, rather than reading the whole collection only to
+		// reject it afterwards.
+		let max_items = MaxItemsPerOp::get() as usize;
+		let items = Assets::owned_in_collection(&class, &owner)
+			.take(max_items.saturating_add(1))
+			.collect::<Vec<_>>();
+		ensure!(items.len() <= max_items, XcmError::TooExpensive);
+		// Apply to every item individually and report back only what actually moved, rather than
+		// bailing out on the first failure and silently leaving an already-burned prefix
+		// unaccounted for.
+		let mut held = xcm_executor::AssetsInHolding::default();
+		for instance in items {
+			match Assets::burn(&class, &instance, Some(&owner)) {
+				Ok(()) => held.subsume(BulkItems::asset_for(&class, &instance)),
+				Err(e) => tracing::debug!(target: LOG_TARGET, ?e, ?class, ?instance, ?owner, "Failed to burn asset in bulk withdraw"),
+			}
+		}
+		Ok(held)
+	}
+
+	fn transfer_asset(
+		what: &Asset,
+		from: &Location,
+		to: &Location,
+		context: &XcmContext,
+	) -> result::Result<xcm_executor::AssetsInHolding, XcmError> {
+		if Matcher::matches_nonfungibles(what).is_ok() {
+			return NonFungiblesTransferAdapter::<Assets, Matcher, AccountIdConverter, AccountId>::transfer_asset(
+				what, from, to, context,
+			)
+		}
+		let class = BulkItems::matches_collection(what)?;
+		let owner =
+			AccountIdConverter::convert_location(from).ok_or(MatchError::AccountIdConversionFailed)?;
+		let destination =
+			AccountIdConverter::convert_location(to).ok_or(MatchError::AccountIdConversionFailed)?;
+		// Bound the enumeration itself: stop reading storage as soon as we know there are more
+		// items than `MaxItemsPerOp` allows, rather than reading the whole collection only to
+		// reject it afterwards.
+		let max_items = MaxItemsPerOp::get() as usize;
+		let items = Assets::owned_in_collection(&class, &owner)
+			.take(max_items.saturating_add(1))
+			.collect::<Vec<_>>();
+		ensure!(items.len() <= max_items, XcmError::TooExpensive);
+		// Apply to every item individually and report back only what actually moved, rather than
+		// bailing out on the first failure and silently leaving an already-transferred prefix
+		// unaccounted for.
+		let mut held = xcm_executor::AssetsInHolding::default();
+		for instance in items {
+			match Assets::transfer(&class, &instance, &destination) {
+				Ok(()) => held.subsume(BulkItems::asset_for(&class, &instance)),
+				Err(e) => tracing::debug!(target: LOG_TARGET, ?e, ?class, ?instance, ?destination, "Failed to transfer asset in bulk transfer"),
+			}
+		}
+		Ok(held)
+	}
+}